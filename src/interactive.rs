@@ -0,0 +1,253 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::parse::Line;
+
+/// Outcome of running the interactive picker.
+pub enum SelectOutcome {
+    /// The user picked a command.
+    Chosen(String),
+    /// Nothing was picked: the user aborted the picker (Esc/Ctrl-C), stdin
+    /// hit EOF, or there were no candidates to pick from.
+    Cancelled,
+    /// Interactive mode couldn't run at all (not a tty, or the terminal
+    /// couldn't be put into raw mode); the caller should fall back to the
+    /// normal output.
+    Unavailable(&'static str),
+}
+
+enum Key {
+    Up,
+    Down,
+    Enter,
+    Backspace,
+    Escape,
+    Char(char),
+    Other,
+}
+
+/// Lets the user arrow through `lines` (optionally typing to filter them)
+/// and, on Enter, returns the selected command's full text. This is a plain
+/// in-process raw-mode loop over the already-ranked `Vec<Line>` -- the same
+/// candidate set the table/json/csv output renders from.
+pub fn select(lines: &[Line]) -> SelectOutcome {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return SelectOutcome::Unavailable("not attached to a tty");
+    }
+
+    if lines.is_empty() {
+        return SelectOutcome::Cancelled;
+    }
+
+    let _raw = match RawMode::enable() {
+        Some(raw) => raw,
+        None => return SelectOutcome::Unavailable("failed to set raw terminal mode"),
+    };
+
+    let mut stdout = io::stdout();
+    let mut filter = String::new();
+    let mut selected = 0usize;
+    let mut rendered_lines = 0usize;
+
+    let outcome = loop {
+        let matches: Vec<&Line> = lines
+            .iter()
+            .filter(|l| filter.is_empty() || l.node.full_text.contains(filter.as_str()))
+            .collect();
+
+        if selected >= matches.len() {
+            selected = matches.len().saturating_sub(1);
+        }
+
+        render(&mut stdout, &mut rendered_lines, &matches, selected, &filter);
+
+        match read_key() {
+            None | Some(Key::Escape) => break SelectOutcome::Cancelled,
+            Some(Key::Up) => selected = selected.saturating_sub(1),
+            Some(Key::Down) => {
+                if selected + 1 < matches.len() {
+                    selected += 1;
+                }
+            }
+            Some(Key::Enter) => {
+                break match matches.get(selected) {
+                    Some(l) => SelectOutcome::Chosen(l.node.full_text.clone()),
+                    None => SelectOutcome::Cancelled,
+                };
+            }
+            Some(Key::Backspace) => {
+                filter.pop();
+            }
+            Some(Key::Char(c)) => filter.push(c),
+            Some(Key::Other) => {}
+        }
+    };
+
+    clear_render(&mut stdout, rendered_lines);
+    outcome
+}
+
+const MAX_VISIBLE: usize = 15;
+
+fn render(stdout: &mut impl Write, rendered_lines: &mut usize, matches: &[&Line], selected: usize, filter: &str) {
+    clear_render(stdout, *rendered_lines);
+
+    let mut lines_written = 0;
+    let _ = write!(stdout, "> {}\r\n", filter);
+    lines_written += 1;
+
+    if matches.is_empty() {
+        let _ = write!(stdout, "  (no matches)\r\n");
+        lines_written += 1;
+    } else {
+        let (start, end) = visible_window(matches.len(), selected);
+        for (offset, l) in matches[start..end].iter().enumerate() {
+            let marker = if start + offset == selected { "> " } else { "  " };
+            let _ = write!(stdout, "{}{}\r\n", marker, l.node.full_text);
+            lines_written += 1;
+        }
+    }
+
+    let _ = stdout.flush();
+    *rendered_lines = lines_written;
+}
+
+/// Picks the `[start, end)` slice of `total` items to render so that
+/// `selected` is always inside the visible window, scrolling as it
+/// approaches either edge instead of just clamping to the first
+/// `MAX_VISIBLE` rows.
+fn visible_window(total: usize, selected: usize) -> (usize, usize) {
+    if total <= MAX_VISIBLE {
+        return (0, total);
+    }
+
+    let mut start = selected.saturating_sub(MAX_VISIBLE / 2);
+    start = start.min(total - MAX_VISIBLE);
+    (start, start + MAX_VISIBLE)
+}
+
+/// Moves the cursor back up over `rendered_lines` worth of output and
+/// clears each one, so the next render (or the caller's own output) starts
+/// from a clean line.
+fn clear_render(stdout: &mut impl Write, rendered_lines: usize) {
+    for _ in 0..rendered_lines {
+        let _ = write!(stdout, "\x1b[1A\x1b[2K");
+    }
+    let _ = write!(stdout, "\r");
+    let _ = stdout.flush();
+}
+
+/// Reads a single keypress from stdin, decoding the arrow-key escape
+/// sequences raw mode otherwise leaves as raw bytes. Returns `None` on
+/// Ctrl-C or EOF.
+fn read_key() -> Option<Key> {
+    let mut stdin = io::stdin();
+    let mut buf = [0u8; 1];
+
+    if stdin.read(&mut buf).ok()? == 0 {
+        return None;
+    }
+
+    match buf[0] {
+        0x03 => None,
+        0x1b => Some(read_escape_sequence(&mut stdin)),
+        b'\r' | b'\n' => Some(Key::Enter),
+        0x7f | 0x08 => Some(Key::Backspace),
+        c if (0x20..0x7f).contains(&c) => Some(Key::Char(c as char)),
+        _ => Some(Key::Other),
+    }
+}
+
+/// Disambiguates a standalone Escape keypress from the `ESC [ A`/`ESC [ B`
+/// sequences arrow keys send. A lone Escape sends exactly one byte and
+/// nothing more, so reading a fixed number of follow-up bytes would block
+/// forever; instead this briefly switches the tty to a short read timeout
+/// so "no more bytes arrived" resolves as plain Escape once that timeout
+/// passes.
+fn read_escape_sequence(stdin: &mut impl Read) -> Key {
+    if !set_read_timeout(true) {
+        return Key::Escape;
+    }
+
+    let mut seq = [0u8; 2];
+    let got = read_available(stdin, &mut seq);
+
+    set_read_timeout(false);
+
+    match (got, &seq) {
+        (2, b"[A") => Key::Up,
+        (2, b"[B") => Key::Down,
+        _ => Key::Escape,
+    }
+}
+
+/// Reads into `buf` until it's full or a read times out (returns `0`),
+/// returning how many bytes were actually collected.
+fn read_available(stdin: &mut impl Read, buf: &mut [u8]) -> usize {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match stdin.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(_) => break,
+        }
+    }
+    filled
+}
+
+/// Toggles the tty between a blocking single-byte read (`min 1 time 0`,
+/// the steady state while waiting for the next keypress) and a short
+/// read timeout (`min 0 time 1`, ~100ms) used only while disambiguating
+/// a possible escape sequence.
+fn set_read_timeout(short: bool) -> bool {
+    let min = if short { "0" } else { "1" };
+    let time = if short { "1" } else { "0" };
+
+    Command::new("stty")
+        .args(["min", min, "time", time])
+        .stdin(Stdio::inherit())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// RAII guard that puts the controlling terminal into raw mode (no echo,
+/// no line buffering) for the lifetime of the picker, restoring the prior
+/// settings on drop.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<Self> {
+        let saved = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()
+            .ok()?;
+        if !saved.status.success() {
+            return None;
+        }
+        let saved = String::from_utf8(saved.stdout).ok()?.trim().to_owned();
+
+        let status = Command::new("stty")
+            .args(["raw", "-echo"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        if !status.success() {
+            return None;
+        }
+
+        Some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .arg(&self.saved)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
@@ -1,11 +1,14 @@
 use structopt::StructOpt;
 
 mod opts;
-use opts::DisplayMode;
+use opts::{DisplayMode, OutputFormat};
 
 mod parse;
 use parse::{Node, CtNode, Line, parse};
 
+mod interactive;
+use interactive::SelectOutcome;
+
 const BARS: &[char] = &[
     ' ',
     '▏',
@@ -19,21 +22,42 @@ const BARS: &[char] = &[
 ];
 
 fn main() {
-    const BARS_WIDE: usize = 8;
-
     let opt = opts::Options::from_args();
     let mode = opt.display.validate();
 
-    let (title, func): (&str, fn(&Node, usize, &str) -> Vec<CtNode>) = match mode {
+    let (title, func): (&str, fn(&Node, usize, &str, f64, usize) -> Vec<CtNode>) = match mode {
         DisplayMode::Fuzzy => ("Fuzzy", Node::top_inclusive_filt),
         DisplayMode::Exact => ("Exact", Node::top_exclusive),
         DisplayMode::Heat => ("Heatmap", Node::top_inclusive),
     };
 
-    let t = parse(opt.file, opt.shell.validate());
+    let t = parse(opt.file, opt.shell.validate(), opt.frecency);
     // println!("{:#?}", t);
 
-    let lines = ct_node_to_list_line(func(&t, opt.count, ""));
+    let lines = ct_node_to_list_line(func(&t, opt.count, "", opt.fuzzy_threshold, opt.min_count));
+
+    if opt.interactive {
+        match interactive::select(&lines) {
+            SelectOutcome::Chosen(cmd) => {
+                println!("{}", cmd);
+                return;
+            }
+            SelectOutcome::Cancelled => return,
+            SelectOutcome::Unavailable(reason) => {
+                eprintln!("{}, falling back to non-interactive output", reason);
+            }
+        }
+    }
+
+    match opt.output {
+        OutputFormat::Table => print_table(title, &lines),
+        OutputFormat::Json => print_json(&lines),
+        OutputFormat::Csv => print_csv(&lines),
+    }
+}
+
+fn print_table(title: &str, lines: &[Line]) {
+    const BARS_WIDE: usize = 8;
 
     println!();
     println!("  {} Commands ", title);
@@ -41,23 +65,75 @@ fn main() {
     println!("|  HEAT    |  COUNT   |  COMMAND ");
     println!("| -------- | -------- | ---------");
 
-    for i in &lines {
+    for i in lines {
         println!("| {} | {:8} | {}", pct_to_bar(i.pct, BARS_WIDE), i.node.count, i.node.full_text);
     }
     println!();
+}
+
+fn print_json(lines: &[Line]) {
+    let mut out = String::from("[");
+
+    for (idx, i) in lines.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!(
+            r#"{{"full_text":{},"count":{},"pct":{}}}"#,
+            json_escape(&i.node.full_text),
+            i.node.count,
+            i.pct,
+        ));
+    }
+
+    out.push(']');
+    println!("{}", out);
+}
+
+fn print_csv(lines: &[Line]) {
+    println!("full_text,count,pct");
+    for i in lines {
+        println!("{},{},{}", csv_escape(&i.node.full_text), i.node.count, i.pct);
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
 
+    out.push('"');
+    out
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
 }
 
 fn ct_node_to_list_line(mut in_dat: Vec<CtNode>) -> Vec<Line> {
     let max = if let Some(item) = in_dat.first() {
-        item.count as f64
+        item.score
     } else {
         return vec![];
     };
 
     in_dat.drain(..).map(|line| {
         Line {
-            pct: (line.count as f64) / max,
+            pct: line.score / max,
             node: line,
         }
     })
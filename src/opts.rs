@@ -13,13 +13,42 @@ pub struct Options {
     #[structopt(flatten)]
     pub shell: ShellOpts,
 
-    /// File to parse. Defaults to history file of selected or detected shell flavor
+    /// File to parse. Defaults to history file of selected or detected shell
+    /// flavor. Pass `-` to read the history stream from stdin instead.
     #[structopt(short = "f", parse(from_os_str))]
     pub file: Option<PathBuf>,
 
     /// How many items to show
     #[structopt(short = "n", default_value = "10")]
     pub count: usize,
+
+    /// Weight commands by recency instead of raw frequency, using the
+    /// timestamps in zsh extended history. Falls back to a flat weight
+    /// when no timestamp is available (e.g. bash history).
+    #[structopt(long = "frecency")]
+    pub frecency: bool,
+
+    /// Output format: `table` (the default ASCII heat-bar view), `json`,
+    /// or `csv`
+    #[structopt(long = "output", default_value = "table")]
+    pub output: OutputFormat,
+
+    /// Interactively arrow through (or type to filter) the ranked commands
+    /// and print the chosen one to stdout. Falls back to the normal output
+    /// when not attached to a tty.
+    #[structopt(short = "i", long = "interactive")]
+    pub interactive: bool,
+
+    /// Minimum exact/inclusive call ratio (0.0-1.0) for a prefix to count as
+    /// a "real" command in `display-fuzzy` mode, instead of one that always
+    /// takes a subcommand (e.g. bare `git`)
+    #[structopt(long = "fuzzy-threshold", default_value = "0.1")]
+    pub fuzzy_threshold: f64,
+
+    /// Prune any command, at any prefix depth, seen fewer than this many
+    /// times before it can appear in the top-N list, in any display mode
+    #[structopt(long = "min-count", default_value = "0")]
+    pub min_count: usize,
 }
 
 #[derive(StructOpt)]
@@ -46,18 +75,26 @@ pub struct ShellOpts {
     /// Manually select Bash history, overriding auto-detect
     #[structopt(long = "flavor-bash")]
     pub bash: bool,
+
+    /// Manually select fish history, overriding auto-detect
+    #[structopt(long = "flavor-fish")]
+    pub fish: bool,
 }
 
 #[derive(Copy, Clone)]
 pub enum HistoryFlavor {
     Zsh,
     Bash,
+    Fish,
 }
 
 impl ShellOpts {
     pub fn detect_shell() -> Option<HistoryFlavor> {
-        const SHELL_MATCHES: &[(&str, HistoryFlavor)] =
-            &[("zsh", HistoryFlavor::Zsh), ("bash", HistoryFlavor::Bash)];
+        const SHELL_MATCHES: &[(&str, HistoryFlavor)] = &[
+            ("zsh", HistoryFlavor::Zsh),
+            ("bash", HistoryFlavor::Bash),
+            ("fish", HistoryFlavor::Fish),
+        ];
 
         let shell_path = env::var("SHELL").ok()?;
 
@@ -71,19 +108,22 @@ impl ShellOpts {
     }
 
     pub fn validate(self) -> HistoryFlavor {
-        match (self.zsh, self.bash) {
-            (false, false) => {
-                if let Some(sh) = Self::detect_shell() {
-                    sh
-                } else {
-                    eject("Unable to detect shell, please manually select a shell flavor");
-                }
-            }
-            (true, false) => HistoryFlavor::Zsh,
-            (false, true) => HistoryFlavor::Bash,
-            (true, true) => {
-                eject("Multiple shell modes selected, please select one or none");
-            }
+        let selected: Vec<HistoryFlavor> = [
+            (self.zsh, HistoryFlavor::Zsh),
+            (self.bash, HistoryFlavor::Bash),
+            (self.fish, HistoryFlavor::Fish),
+        ]
+        .iter()
+        .filter(|(sel, _)| *sel)
+        .map(|(_, flavor)| *flavor)
+        .collect();
+
+        match selected.len() {
+            0 => Self::detect_shell().unwrap_or_else(|| {
+                eject("Unable to detect shell, please manually select a shell flavor");
+            }),
+            1 => selected[0],
+            _ => eject("Multiple shell modes selected, please select one or none"),
         }
     }
 }
@@ -93,33 +133,86 @@ impl HistoryFlavor {
         use HistoryFlavor::*;
 
         if let Ok(hist_file) = std::env::var("HISTFILE") {
-            PathBuf::from(hist_file)
-        } else {
-            let name = match self {
-                Zsh => ".zsh_history",
-                Bash => ".bash_history",
-            };
-
-            let mut dir = home_dir().unwrap_or_else(|| {
-                eject("Unable to determine home path. Please specify history file path");
-            });
-            dir.push(name);
-            dir
+            return PathBuf::from(hist_file);
         }
+
+        let mut dir = home_dir().unwrap_or_else(|| {
+            eject("Unable to determine home path. Please specify history file path");
+        });
+
+        match self {
+            Zsh => dir.push(".zsh_history"),
+            Bash => dir.push(".bash_history"),
+            Fish => {
+                dir.push(".local");
+                dir.push("share");
+                dir.push("fish");
+                dir.push("fish_history");
+            }
+        }
+
+        dir
     }
 
-    pub fn regex_and_capture_idx(&self) -> (Regex, usize) {
+    /// Returns the capture regex, the capture group index of the command
+    /// text, and (for zsh extended history only) the capture group index of
+    /// the entry's unix-epoch timestamp, used for `--frecency`. Bash and fish
+    /// return `None`; fish's `when:` lines are not captured.
+    pub fn regex_and_capture_idx(&self) -> (Regex, usize, Option<usize>) {
         use HistoryFlavor::*;
-        let (re_res, idx) = match self {
-            Zsh => (Regex::new(r"^.*;(sudo )?(.*)$"), 2),
-            Bash => (Regex::new(r"^(sudo )?(.*)$"), 2),
+        let (re_res, cmd_idx, ts_idx) = match self {
+            // zsh extended history: `: 1700000000:12;sudo git status`
+            Zsh => (Regex::new(r"^: (\d+):\d+;(sudo )?(.*)$"), 3, Some(1)),
+            Bash => (Regex::new(r"^(sudo )?(.*)$"), 2, None),
+            // fish history: a YAML-ish list of `- cmd: <command>` / `  when: <epoch>` records
+            Fish => (Regex::new(r"^- cmd: (.*)$"), 1, None),
         };
 
         (
             re_res.unwrap_or_else(|_| eject("Failed to compile regex!")),
-            idx,
+            cmd_idx,
+            ts_idx,
         )
     }
+
+    /// Post-processes a single already-whitespace-split token. Fish escapes
+    /// embedded newlines and quotes with backslashes when it writes a
+    /// `cmd:` entry; undo that so the stored token matches the command as
+    /// it was actually run. This must run *after* tokenization, not on the
+    /// raw captured line: unescaping first would turn an escaped newline
+    /// inside a quoted argument (e.g. `"a\nb"`) into a real newline before
+    /// the whitespace split sees it, splitting one argument into two.
+    pub fn unescape_token(&self, raw: &str) -> String {
+        match self {
+            HistoryFlavor::Fish => unescape_fish_cmd(raw),
+            _ => raw.to_owned(),
+        }
+    }
+}
+
+fn unescape_fish_cmd(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+
+    out
 }
 
 pub enum DisplayMode {
@@ -128,6 +221,29 @@ pub enum DisplayMode {
     Heat,
 }
 
+#[derive(Copy, Clone)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(format!(
+                "unknown output format '{}', expected one of: table, json, csv",
+                other
+            )),
+        }
+    }
+}
+
 impl DisplayOpts {
     pub fn validate(self) -> DisplayMode {
         match (self.fuzzy, self.exact, self.heat) {
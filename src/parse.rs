@@ -3,7 +3,8 @@ use std::{
     collections::{BinaryHeap, BTreeMap},
     fs::File,
     io::{self, prelude::*, BufReader},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::opts::HistoryFlavor;
@@ -11,16 +12,19 @@ use crate::eject;
 
 
 /// CtNode are post-processed partial/full commands with an associated non-specific count
-#[derive(Eq, Debug)]
+#[derive(Debug)]
 pub struct CtNode {
     pub count: usize,
+    pub score: f64,
     pub full_text: String,
 }
 
+impl Eq for CtNode {}
+
 impl Ord for CtNode {
     fn cmp(&self, other: &CtNode) -> Ordering {
         // Why does this need to be backwards?
-        other.count.cmp(&self.count)
+        other.score.partial_cmp(&self.score).unwrap_or(Ordering::Equal)
     }
 }
 
@@ -32,7 +36,7 @@ impl PartialOrd for CtNode {
 
 impl PartialEq for CtNode {
     fn eq(&self, other: &CtNode) -> bool {
-        self.count == other.count
+        self.score == other.score
     }
 }
 
@@ -45,12 +49,16 @@ pub struct Line {
 
 /// A Node is a recursive structure that counts the number of times
 /// it has been called (`count_exact`), and itself + sum(all children) have
-/// been called (`count_inclusive`).
+/// been called (`count_inclusive`). Alongside each raw count, a weighted
+/// `f64` score is accumulated so frecency-aware ranking can be layered on
+/// top without disturbing the plain counts used for display.
 #[derive(Debug)]
 pub struct Node {
     pub children: BTreeMap<String, Node>,
     pub count_inclusive: usize,
     pub count_exact: usize,
+    pub score_inclusive: f64,
+    pub score_exact: f64,
 }
 
 impl Node {
@@ -59,14 +67,20 @@ impl Node {
             children: BTreeMap::new(),
             count_inclusive: 0,
             count_exact: 0,
+            score_inclusive: 0.0,
+            score_exact: 0.0,
         }
     }
 
-    /// Recursively chomp string tokens
-    pub fn chomp(&mut self, toks: &[String]) {
+    /// Recursively chomp string tokens, accumulating `weight` into the
+    /// score of every node along the path. When frecency isn't in play,
+    /// callers pass a flat `1.0`, so `score_*` tracks `count_*` exactly.
+    pub fn chomp(&mut self, toks: &[String], weight: f64) {
         self.count_inclusive += 1;
+        self.score_inclusive += weight;
         if toks.is_empty() {
             self.count_exact += 1;
+            self.score_exact += weight;
             return;
         }
 
@@ -77,24 +91,30 @@ impl Node {
         // We guarantee above the children contain this token
         let child = self.children.get_mut(&toks[0]).unwrap();
 
-        child.chomp(&toks[1..]);
+        child.chomp(&toks[1..], weight);
     }
 
     /// Get the top `ct` items that have been called exactly. This
-    /// powers the `display-exact` mode
-    pub fn top_exclusive(&self, ct: usize, prefix: &str) -> Vec<CtNode> {
+    /// powers the `display-exact` mode. Nodes seen fewer than `min_count`
+    /// times are pruned before they can enter the top-N heap. `_threshold`
+    /// is unused here and only exists so this shares a signature with
+    /// [`Node::top_inclusive_filt`].
+    pub fn top_exclusive(&self, ct: usize, prefix: &str, _threshold: f64, min_count: usize) -> Vec<CtNode> {
         let mut topn: BinaryHeap<CtNode> = BinaryHeap::new();
         self.children.iter().for_each(|(cmd, node)| {
             let next_txt = format!("{}{} ", prefix, cmd);
-            node.top_exclusive(ct, &next_txt)
+            node.top_exclusive(ct, &next_txt, _threshold, min_count)
                 .drain(..)
                 .for_each(|t| {
                     topn.push(t)
                 });
-            topn.push(CtNode {
-                count: node.count_exact,
-                full_text: next_txt.trim_end().to_owned(),
-            });
+            if node.count_exact >= min_count {
+                topn.push(CtNode {
+                    count: node.count_exact,
+                    score: node.score_exact,
+                    full_text: next_txt.trim_end().to_owned(),
+                });
+            }
 
         });
         while topn.len() > ct {
@@ -107,20 +127,26 @@ impl Node {
     }
 
     /// Get the top `ct` items that have been called or who's children have
-    /// been called. This powers the `display-heat` mode
-    pub fn top_inclusive(&self, ct: usize, prefix: &str) -> Vec<CtNode> {
+    /// been called. This powers the `display-heat` mode. Nodes seen fewer
+    /// than `min_count` times are pruned before they can enter the top-N
+    /// heap. `_threshold` is unused here and only exists so this shares a
+    /// signature with [`Node::top_inclusive_filt`].
+    pub fn top_inclusive(&self, ct: usize, prefix: &str, _threshold: f64, min_count: usize) -> Vec<CtNode> {
         let mut topn: BinaryHeap<CtNode> = BinaryHeap::new();
         self.children.iter().for_each(|(cmd, node)| {
             let next_txt = format!("{}{} ", prefix, cmd);
-            node.top_inclusive(ct, &next_txt)
+            node.top_inclusive(ct, &next_txt, _threshold, min_count)
                 .drain(..)
                 .for_each(|t| {
                     topn.push(t)
                 });
-            topn.push(CtNode {
-                count: node.count_inclusive,
-                full_text: next_txt.trim_end().to_owned(),
-            });
+            if node.count_inclusive >= min_count {
+                topn.push(CtNode {
+                    count: node.count_inclusive,
+                    score: node.score_inclusive,
+                    full_text: next_txt.trim_end().to_owned(),
+                });
+            }
 
         });
         while topn.len() > ct {
@@ -135,20 +161,25 @@ impl Node {
     /// Get the top `ct` items that have been called or who's children have
     /// been called. However, attempt to filter out nodes that are never directly
     /// called, to get rid of items that ALWAYS have a subcommand, like `git`.
-    /// This function powers the `display-fuzzy` command.
-    pub fn top_inclusive_filt(&self, ct: usize, prefix: &str) -> Vec<CtNode> {
+    /// This function powers the `display-fuzzy` command. `threshold` is the
+    /// minimum fraction of exact-to-inclusive calls (0.0-1.0) for a prefix to
+    /// count as "real"; nodes seen fewer than `min_count` times are pruned
+    /// before they can enter the top-N heap.
+    pub fn top_inclusive_filt(&self, ct: usize, prefix: &str, threshold: f64, min_count: usize) -> Vec<CtNode> {
         let mut topn: BinaryHeap<CtNode> = BinaryHeap::new();
         self.children.iter().for_each(|(cmd, node)| {
             let next_txt = format!("{}{} ", prefix, cmd);
-            node.top_inclusive_filt(ct, &next_txt)
+            node.top_inclusive_filt(ct, &next_txt, threshold, min_count)
                 .drain(..)
                 .for_each(|t| {
                     topn.push(t)
                 });
 
-            if (node.count_exact != 0) && (((node.count_exact * 10) / node.count_inclusive) >= 1) {
+            let exact_ratio = node.count_exact as f64 / node.count_inclusive as f64;
+            if node.count_inclusive >= min_count && node.count_exact != 0 && exact_ratio >= threshold {
                 topn.push(CtNode {
                     count: node.count_inclusive,
+                    score: node.score_inclusive,
                     full_text: next_txt.trim_end().to_owned(),
                 });
             }
@@ -164,34 +195,76 @@ impl Node {
     }
 }
 
-pub fn parse(path: Option<PathBuf>, flavor: HistoryFlavor) -> Node  {
-    let mut tree = Node::new();
+/// Tiered frecency weight for a command last run `cmd_epoch` seconds since
+/// the unix epoch, relative to `now_epoch`: used within the last hour scores
+/// highest, tapering off down to a floor for anything older than a week.
+fn frecency_weight(cmd_epoch: u64, now_epoch: u64) -> f64 {
+    match now_epoch.saturating_sub(cmd_epoch) {
+        age if age <= 3_600 => 4.0,
+        age if age <= 86_400 => 2.0,
+        age if age <= 604_800 => 0.5,
+        _ => 0.25,
+    }
+}
 
-    let path = path.unwrap_or_else(|| {
-        flavor.history_path()
-    });
-    let (re, idx) = flavor.regex_and_capture_idx();
+/// Opens the history source: stdin when `path` is the `-` sentinel,
+/// otherwise the given path, falling back to the shell flavor's default
+/// history file when no path was given at all.
+fn open_source(path: Option<PathBuf>, flavor: HistoryFlavor) -> Box<dyn BufRead> {
+    if let Some(p) = &path {
+        if p == Path::new("-") {
+            return Box::new(BufReader::new(io::stdin()));
+        }
+    }
 
+    let path = path.unwrap_or_else(|| flavor.history_path());
     let f = File::open(&path).unwrap_or_else(|_| {
         eject(&format!("Unable to open specified or detected history file: {:?}", path));
     });
-    let f = BufReader::new(f);
+    Box::new(BufReader::new(f))
+}
+
+pub fn parse(path: Option<PathBuf>, flavor: HistoryFlavor, frecency: bool) -> Node  {
+    let mut tree = Node::new();
+
+    let f = open_source(path, flavor);
+    let (re, cmd_idx, ts_idx) = flavor.regex_and_capture_idx();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
 
     let _: io::Result<()> = f
         .lines()
         .filter_map(|line| line.ok())
-        .filter_map(|line| Some(
-            re
-                .captures(&line)?
-                .get(idx)?
-                .as_str()
-                .to_string()
-            )
-        )
-        .try_for_each(|lineout| {
-            let toks = lineout.split_whitespace().map(|t| t.to_string()).collect::<Vec<String>>();
-
-            tree.chomp(&toks);
+        .filter_map(|line| {
+            let caps = re.captures(&line)?;
+            let cmd = caps.get(cmd_idx)?.as_str().to_string();
+
+            let weight = if frecency {
+                ts_idx
+                    .and_then(|idx| caps.get(idx))
+                    .and_then(|m| m.as_str().parse::<u64>().ok())
+                    .map(|ts| frecency_weight(ts, now))
+                    .unwrap_or(1.0)
+            } else {
+                1.0
+            };
+
+            Some((cmd, weight))
+        })
+        .try_for_each(|(lineout, weight)| {
+            // Split on the still-escaped text first, then unescape each
+            // token: an escaped newline inside a quoted argument is two
+            // ordinary (non-whitespace) characters until it's unescaped,
+            // so splitting first keeps it from being read as a separator.
+            let toks = lineout
+                .split_whitespace()
+                .map(|t| flavor.unescape_token(t))
+                .collect::<Vec<String>>();
+
+            tree.chomp(&toks, weight);
             Ok(())
         }
     );